@@ -3,7 +3,64 @@ use std::path::PathBuf;
 #[derive(argh::FromArgs)]
 /// An interpreter for a simple instruction language that I made for fun.
 pub struct Args {
+    #[argh(subcommand)]
+    pub cmd: Command,
+}
+
+#[derive(argh::FromArgs)]
+#[argh(subcommand)]
+pub enum Command {
+    Run(RunArgs),
+    Repl(ReplArgs),
+    Debug(DebugArgs),
+    Emit(EmitArgs),
+}
+
+#[derive(argh::FromArgs)]
+/// run a program from a file
+#[argh(subcommand, name = "run")]
+pub struct RunArgs {
     #[argh(positional)]
     /// the path to the program to run
     pub path: PathBuf,
+
+    #[argh(option)]
+    /// seed for `rnd`'s random number generator; nondeterministic if unset
+    pub seed: Option<u64>,
+}
+
+#[derive(argh::FromArgs)]
+/// start an interactive session
+#[argh(subcommand, name = "repl")]
+pub struct ReplArgs {
+    #[argh(option)]
+    /// seed for `rnd`'s random number generator; nondeterministic if unset
+    pub seed: Option<u64>,
+}
+
+#[derive(argh::FromArgs)]
+/// step through a program under an interactive debugger
+#[argh(subcommand, name = "debug")]
+pub struct DebugArgs {
+    #[argh(positional)]
+    /// the path to the program to debug
+    pub path: PathBuf,
+
+    #[argh(option)]
+    /// seed for `rnd`'s random number generator; nondeterministic if unset
+    pub seed: Option<u64>,
+}
+
+#[derive(argh::FromArgs)]
+/// transpile a program to standalone C, printed to stdout
+#[argh(subcommand, name = "emit")]
+pub struct EmitArgs {
+    #[argh(positional)]
+    /// the path to the program to transpile
+    pub path: PathBuf,
+
+    #[argh(option)]
+    /// seed to bake into the emitted program's `rng_state` initializer; seeds from `time(NULL)`
+    /// at the emitted program's own runtime if unset, same as leaving `--seed` off `run`/`debug`
+    pub seed: Option<u64>,
 }
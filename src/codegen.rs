@@ -0,0 +1,221 @@
+use crate::instrs::{Instr, Reg, Val};
+
+use std::{collections::HashMap, fmt::Write};
+
+fn reg_c(reg: Reg) -> &'static str {
+    match reg {
+        Reg::X => "x",
+        Reg::Acc => "acc",
+    }
+}
+
+fn val_c(val: Val) -> String {
+    match val {
+        Val::Num(n) => n.to_string(),
+        Val::X => "x".to_string(),
+    }
+}
+
+const PRELUDE: &str = r#"#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <time.h>
+
+static long *tape;
+static size_t tape_len;
+
+static uint64_t rng_state;
+
+static uint64_t rnd_next(void) {
+    rng_state += 0x9E3779B97F4A7C15ULL;
+    uint64_t z = rng_state;
+    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ULL;
+    z = (z ^ (z >> 27)) * 0x94D049BB133111EBULL;
+    return z ^ (z >> 31);
+}
+
+static void grow(size_t cursor) {
+    if (cursor >= tape_len) {
+        size_t new_len = cursor + 1;
+        tape = realloc(tape, new_len * sizeof(long));
+        for (size_t i = tape_len; i < new_len; i++) tape[i] = 0;
+        tape_len = new_len;
+    }
+}
+
+static int is_scalar(long n) {
+    return n >= 0 && n <= 0x10FFFFL && !(n >= 0xD800L && n <= 0xDFFFL);
+}
+
+static void put_utf8(long scalar) {
+    unsigned long c = (unsigned long)scalar;
+    if (c < 0x80) {
+        putchar((int)c);
+    } else if (c < 0x800) {
+        putchar((int)(0xC0 | (c >> 6)));
+        putchar((int)(0x80 | (c & 0x3F)));
+    } else if (c < 0x10000) {
+        putchar((int)(0xE0 | (c >> 12)));
+        putchar((int)(0x80 | ((c >> 6) & 0x3F)));
+        putchar((int)(0x80 | (c & 0x3F)));
+    } else {
+        putchar((int)(0xF0 | (c >> 18)));
+        putchar((int)(0x80 | ((c >> 12) & 0x3F)));
+        putchar((int)(0x80 | ((c >> 6) & 0x3F)));
+        putchar((int)(0x80 | (c & 0x3F)));
+    }
+}
+"#;
+
+/// Lowers a resolved program to self-contained C source that replicates the interpreter's
+/// semantics instruction-for-instruction, except as noted below. Jump targets are already instruction indices (see
+/// [`crate::parse::resolve`]), so the body is emitted as a `switch (ip)` dispatch loop with one
+/// case per instruction; `sav`/`ret` set `ip` to an arbitrary computed value and fall straight
+/// back into the loop, so computed jumps work the same as in the interpreter. `seed`, if given,
+/// is baked into the emitted program's `rng_state` initializer so it reproduces a `rnd` sequence
+/// the same way `--seed` does for `run`/`debug`/`repl`; left unset, the emitted program seeds
+/// itself from `time(NULL)` at its own runtime instead.
+///
+/// Two instructions differ from the interpreter: `inp` reads one byte per call straight from
+/// stdin with `getchar`, without the interpreter's line buffering, and `ret` from a negative value
+/// is cast to `size_t` rather than reported as an error.
+pub fn emit_c(program: &[Instr], labels: &HashMap<String, usize>, seed: Option<u64>) -> String {
+    let names: HashMap<usize, &str> = labels.iter().map(|(name, &idx)| (idx, name.as_str())).collect();
+
+    let mut out = String::new();
+    out.push_str(PRELUDE);
+    out.push_str("\nint main(void) {\n");
+    out.push_str("    long x = 0, acc = 0;\n");
+    out.push_str("    size_t cursor = 0;\n");
+    out.push_str("    size_t ip = 0;\n");
+    match seed {
+        Some(seed) => writeln!(out, "    rng_state = {seed}ULL;").unwrap(),
+        None => out.push_str("    rng_state = (uint64_t)time(NULL);\n"),
+    }
+    out.push_str("    grow(0);\n\n");
+    out.push_str("    for (;;) {\n");
+    out.push_str("        switch (ip) {\n");
+
+    for (idx, instr) in program.iter().enumerate() {
+        if let Some(name) = names.get(&idx) {
+            writeln!(out, "        // {name}:").unwrap();
+        }
+        writeln!(out, "        case {idx}:").unwrap();
+
+        match instr {
+            Instr::Gol => {
+                out.push_str("            if (cursor > 0) cursor--;\n");
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Gor => {
+                out.push_str("            cursor++;\n");
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Get(reg) => {
+                writeln!(out, "            grow(cursor); tape[cursor] = {};", reg_c(*reg)).unwrap();
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Put(reg) => {
+                writeln!(out, "            grow(cursor); {} = tape[cursor];", reg_c(*reg)).unwrap();
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Jmp(target) => {
+                writeln!(out, "            ip = {target};").unwrap();
+                out.push_str("            break;\n");
+            }
+            Instr::Jnz(reg, target) => {
+                writeln!(
+                    out,
+                    "            if ({} != 0) {{ ip = {target}; break; }}",
+                    reg_c(*reg)
+                )
+                .unwrap();
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Jlz(reg, target) => {
+                writeln!(
+                    out,
+                    "            if ({} < 0) {{ ip = {target}; break; }}",
+                    reg_c(*reg)
+                )
+                .unwrap();
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Sav => {
+                out.push_str("            grow(cursor); tape[cursor] = (long)ip;\n");
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Ret => {
+                // `run.rs`'s `ret()` never sets the interpreter's `jumped` flag, so every `ret`
+                // falls into the same auto-increment as a non-jumping instruction; mirror that
+                // here instead of jumping straight to the saved value.
+                out.push_str("            grow(cursor); ip = (size_t)tape[cursor] + 1;\n");
+                out.push_str("            break;\n");
+            }
+            Instr::Inp => {
+                out.push_str("            grow(cursor); tape[cursor] = getchar();\n");
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Out => {
+                out.push_str("            grow(cursor);\n");
+                out.push_str("            if (is_scalar(tape[cursor])) {\n");
+                out.push_str("                printf(\"(%ld: \", tape[cursor]);\n");
+                out.push_str("                put_utf8(tape[cursor]);\n");
+                out.push_str("                printf(\")\");\n");
+                out.push_str("            }\n");
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Set(val) => {
+                writeln!(out, "            grow(cursor); tape[cursor] = {};", val_c(*val)).unwrap();
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Add(val) => {
+                writeln!(out, "            grow(cursor); tape[cursor] += {};", val_c(*val)).unwrap();
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Mul(val) => {
+                writeln!(out, "            grow(cursor); tape[cursor] *= {};", val_c(*val)).unwrap();
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Div(val) => {
+                writeln!(out, "            grow(cursor); tape[cursor] /= {};", val_c(*val)).unwrap();
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Dec => {
+                out.push_str("            acc--;\n");
+                out.push_str("            ip++; break;\n");
+            }
+            Instr::Rnd(val) => {
+                out.push_str("            grow(cursor);\n");
+                match val {
+                    Val::Num(0) => {
+                        out.push_str("            tape[cursor] = (long)rnd_next();\n");
+                    }
+                    Val::Num(n) if *n < 0 => {
+                        writeln!(
+                            out,
+                            "            fprintf(stderr, \"`rnd` bound must be positive, got {n}\\n\"); exit(1);"
+                        )
+                        .unwrap();
+                    }
+                    Val::Num(n) => {
+                        writeln!(out, "            tape[cursor] = (long)(rnd_next() % {n}ULL);").unwrap();
+                    }
+                    Val::X => {
+                        out.push_str("            if (x <= 0) { fprintf(stderr, \"`rnd x` requires a positive value in `x`\\n\"); exit(1); }\n");
+                        out.push_str("            tape[cursor] = (long)(rnd_next() % (uint64_t)x);\n");
+                    }
+                }
+                out.push_str("            ip++; break;\n");
+            }
+        }
+    }
+
+    out.push_str("        default:\n");
+    out.push_str("            return 0;\n");
+    out.push_str("        }\n");
+    out.push_str("    }\n");
+    out.push_str("}\n");
+
+    out
+}
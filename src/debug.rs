@@ -0,0 +1,87 @@
+use crate::{args::DebugArgs, parse_and_resolve, run::State, Resolved};
+
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+};
+
+use anyhow::{Context, Error};
+use ariadne::{Color, Fmt, Label, Report, ReportKind, Source};
+
+/// Prints the source with the instruction about to execute underlined, using the same
+/// `ariadne` machinery as the parser's diagnostics.
+fn highlight_current(src: &str, path: &str, state: &State) {
+    let Some(span) = state.current_span() else {
+        println!("{}", "(program has exited)".fg(Color::Yellow));
+        return;
+    };
+
+    Report::build(ReportKind::Advice, path, span.start)
+        .with_message(format!("stopped at instruction {}", state.instr_ptr()))
+        .with_label(
+            Label::new((path, span))
+                .with_message("about to execute this")
+                .with_color(Color::Cyan),
+        )
+        .finish()
+        .print((path, Source::from(src)))
+        .unwrap();
+}
+
+pub fn run(args: &DebugArgs) -> Result<(), Error> {
+    let src = fs::read_to_string(&args.path).context("failed to read input file")?;
+    let path = args.path.to_string_lossy();
+
+    let Resolved::Program(program, spans, labels) = parse_and_resolve(&src, &path) else {
+        return Ok(());
+    };
+
+    let mut state = State::new(program, spans, args.seed);
+    let mut breakpoints = HashSet::new();
+
+    loop {
+        highlight_current(&src, &path, &state);
+        state.print_machine();
+
+        print!("(debug) ");
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("step" | "s") => {
+                if !state.step()? {
+                    println!("program exited");
+                    return Ok(());
+                }
+            }
+            Some("continue" | "c") => loop {
+                if !state.step()? {
+                    println!("program exited");
+                    return Ok(());
+                }
+                if breakpoints.contains(&state.instr_ptr()) {
+                    break;
+                }
+            },
+            Some("print" | "p") => state.print_machine(),
+            Some("break" | "b") => match words.next() {
+                Some(label) => match labels.get(label) {
+                    Some(&idx) => {
+                        breakpoints.insert(idx);
+                        println!("breakpoint set at `{label}` (instruction {idx})");
+                    }
+                    None => println!("unknown label `{label}`"),
+                },
+                None => println!("usage: break <label>"),
+            },
+            Some("quit" | "q") => return Ok(()),
+            _ => println!("commands: step, continue, print, break <label>, quit"),
+        }
+    }
+}
@@ -12,8 +12,11 @@ pub enum Val {
     X,
 }
 
+/// An instruction, generic over how it refers to a jump target: [`Token`] right after parsing
+/// (for diagnostics), and `usize` once [`crate::parse::resolve`] has looked labels up to their
+/// instruction index.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Instr {
+pub enum Instr<L = usize> {
     /// Move the cursor left. If the cursor is already at the beginning of the tape, do nothing.
     Gol,
 
@@ -27,13 +30,13 @@ pub enum Instr {
     Put(Reg),
 
     /// Unconditionally move the instruction pointer to the given label.
-    Jmp(Token),
+    Jmp(L),
 
     /// Jump to the given label if the value in the given register is not 0.
-    Jnz(Reg, Token),
+    Jnz(Reg, L),
 
     /// Jump to the given label if the vaule in the given register is less than 0.
-    Jlz(Reg, Token),
+    Jlz(Reg, L),
 
     /// Store the current value of the instruction pointer in the value at the cursor
     Sav,
@@ -63,4 +66,10 @@ pub enum Instr {
 
     /// Decrement `acc`.
     Dec,
+
+    /// Set the value at the cursor to a random integer in `[0, val)`, or a full-range random
+    /// integer if `val` is the literal `0`.
+    Rnd(Val),
 }
+
+pub type RawInstr = Instr<Token>;
@@ -1,6 +1,10 @@
-use crate::{args::Args, run::State};
+use crate::{
+    args::{Args, Command},
+    instrs::Instr,
+    run::State,
+};
 
-use std::fs;
+use std::{collections::HashMap, fs, ops::Range};
 
 use anyhow::{Context, Error};
 
@@ -16,39 +20,90 @@ mod args;
 
 mod err;
 
-#[cfg(test)]
-mod tests;
+mod repl;
 
-fn main() -> Result<(), Error> {
-    env_logger::init();
+mod debug;
 
-    let args: Args = argh::from_env();
-    let src = fs::read_to_string(&args.path).context("failed to read input file")?;
+mod codegen;
 
-    let (ast, errs) = parse::root().parse_recovery(src.as_str());
+mod rng;
 
-    let path = args.path.to_string_lossy();
+#[cfg(test)]
+mod tests;
+
+/// The outcome of parsing and resolving a source string, after any errors have already been
+/// reported via [`err::emit_parse_error`]/[`err::emit_label_error`].
+pub enum Resolved {
+    Program(Vec<Instr>, Vec<Range<usize>>, HashMap<String, usize>),
+    ParseFailed,
+    LabelFailed,
+}
+
+/// Parses `src` and resolves its labels, emitting diagnostics against `path` for any parse or
+/// label errors along the way. Shared by every entry point (`run`, `emit`, `debug`, `repl`) so
+/// they agree on what counts as an error and how it's reported.
+pub fn parse_and_resolve(src: &str, path: &str) -> Resolved {
+    let (ast, errs) = parse::root().parse_recovery(src);
 
     if !errs.is_empty() {
         for err in errs {
-            err::emit_parse_error(&src, err, &path);
+            err::emit_parse_error(src, err, path);
         }
+        return Resolved::ParseFailed;
     }
 
-    if let Some(ast) = ast {
-        let (program, table) = match parse::resolve(ast) {
-            Ok(x) => x,
-            Err(errs) => {
-                for err in errs {
-                    err::emit_label_error(err, &src, &path);
-                }
+    let Some(ast) = ast else {
+        return Resolved::ParseFailed;
+    };
 
-                return Ok(());
+    match parse::resolve(ast) {
+        Ok((program, spans, labels)) => Resolved::Program(program, spans, labels),
+        Err(errs) => {
+            for err in errs {
+                err::emit_label_error(err, src, path);
             }
-        };
-        let mut state = State::new(&program, table);
-        state.run()?;
+
+            Resolved::LabelFailed
+        }
     }
+}
+
+fn run_file(args: &args::RunArgs) -> Result<(), Error> {
+    let src = fs::read_to_string(&args.path).context("failed to read input file")?;
+    let path = args.path.to_string_lossy();
+
+    let Resolved::Program(program, spans, _labels) = parse_and_resolve(&src, &path) else {
+        return Ok(());
+    };
+
+    let mut state = State::new(program, spans, args.seed);
+    state.run()?;
+
+    Ok(())
+}
+
+fn emit(args: &args::EmitArgs) -> Result<(), Error> {
+    let src = fs::read_to_string(&args.path).context("failed to read input file")?;
+    let path = args.path.to_string_lossy();
+
+    let Resolved::Program(program, _spans, labels) = parse_and_resolve(&src, &path) else {
+        return Ok(());
+    };
+
+    print!("{}", codegen::emit_c(&program, &labels, args.seed));
 
     Ok(())
 }
+
+fn main() -> Result<(), Error> {
+    env_logger::init();
+
+    let args: Args = argh::from_env();
+
+    match &args.cmd {
+        Command::Run(args) => run_file(args),
+        Command::Repl(args) => repl::run(args),
+        Command::Debug(args) => debug::run(args),
+        Command::Emit(args) => emit(args),
+    }
+}
@@ -1,4 +1,4 @@
-use crate::instrs::{Instr, Reg, Val};
+use crate::instrs::{Instr, RawInstr, Reg, Val};
 
 use std::{collections::HashMap, ops::Range};
 
@@ -15,8 +15,9 @@ type Ast = Vec<AstLine>;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AstLine {
-    Instr(Instr),
+    Instr(RawInstr, Range<usize>),
     Label(Token),
+    Comment,
     Error,
 }
 
@@ -39,6 +40,56 @@ fn val() -> impl Parser<char, Val, Error = Simple<char>> {
         .labelled("number or x")
 }
 
+/// A zero-width negative lookahead: succeeds without consuming input iff `p` would fail to
+/// parse starting at the current position.
+fn not<P, O>(p: P) -> impl Parser<char, (), Error = Simple<char>> + Clone
+where
+    P: Parser<char, O, Error = Simple<char>> + Clone,
+{
+    p.rewind().or_not().try_map(|found, span| {
+        if found.is_some() {
+            Err(Simple::custom(span, "unexpected token"))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// A `#{ ... }#` block comment, which may nest. Tracks its own open span so an unterminated
+/// block is reported at the opening `#{`, mirroring how `SimpleReason::Unclosed` is surfaced
+/// in `err::emit_parse_error`.
+fn block_comment() -> impl Parser<char, (), Error = Simple<char>> + Clone {
+    recursive(|block_comment| {
+        let body = block_comment.or(not(just("}#")).ignore_then(any()).ignored());
+
+        just("#{")
+            .map_with_span(|_, span| span)
+            .then_ignore(body.repeated())
+            .then(just("}#").or_not())
+            .try_map(|(open_span, closed), _| {
+                if closed.is_some() {
+                    Ok(())
+                } else {
+                    Err(Simple::custom(open_span, "unterminated block comment"))
+                }
+            })
+    })
+}
+
+/// A `#`-to-end-of-line comment. Excludes `#{`, which always starts a block comment instead, so
+/// an unterminated block comment is reported as such rather than silently falling back to this
+/// parser and swallowing the rest of the line.
+fn line_comment() -> impl Parser<char, (), Error = Simple<char>> + Clone {
+    just('#')
+        .then_ignore(not(just('{')))
+        .then(filter(|c: &char| *c != '\n').repeated())
+        .ignored()
+}
+
+fn comment() -> impl Parser<char, (), Error = Simple<char>> + Clone {
+    block_comment().or(line_comment()).labelled("comment")
+}
+
 pub fn root() -> impl Parser<char, Ast, Error = Simple<char>> {
     let space = || {
         one_of("\t ")
@@ -81,16 +132,19 @@ pub fn root() -> impl Parser<char, Ast, Error = Simple<char>> {
         just("mul").then(space()).ignore_then(val()).map(Instr::Mul),
         just("div").then(space()).ignore_then(val()).map(Instr::Div),
         just("dec").to(Instr::Dec),
+        just("rnd").then(space()).ignore_then(val()).map(Instr::Rnd),
     ))
-    .map(AstLine::Instr)
+    .map_with_span(AstLine::Instr)
     .labelled("instruction")
     .or(text::ident()
         .then_ignore(just(":"))
         .map_with_span(|inner, span| AstLine::Label(Token { inner, span }))
         .labelled("label"))
+    .or(comment().to(AstLine::Comment))
     .recover_with(skip_until(['\n'], |_| AstLine::Error))
     .then_ignore(
         space0
+            .then(comment().or_not())
             .then(text::newline())
             .labelled("trailing newline")
             .then(text::whitespace()),
@@ -99,6 +153,7 @@ pub fn root() -> impl Parser<char, Ast, Error = Simple<char>> {
     .then_ignore(end())
 }
 
+#[derive(Debug)]
 pub enum LabelError {
     Unknown(Token),
     Redefined {
@@ -108,51 +163,80 @@ pub enum LabelError {
     },
 }
 
-pub fn resolve(ast: Ast) -> Result<(Vec<Instr>, HashMap<String, usize>), Vec<LabelError>> {
+/// Lowers a jump target from a [`Token`] naming a label to the resolved instruction index,
+/// leaving every other variant untouched.
+fn lower(instr: RawInstr, labels: &HashMap<String, usize>) -> Instr {
+    match instr {
+        Instr::Jmp(token) => Instr::Jmp(labels[&token.inner]),
+        Instr::Jnz(reg, token) => Instr::Jnz(reg, labels[&token.inner]),
+        Instr::Jlz(reg, token) => Instr::Jlz(reg, labels[&token.inner]),
+        Instr::Gol => Instr::Gol,
+        Instr::Gor => Instr::Gor,
+        Instr::Get(reg) => Instr::Get(reg),
+        Instr::Put(reg) => Instr::Put(reg),
+        Instr::Sav => Instr::Sav,
+        Instr::Ret => Instr::Ret,
+        Instr::Inp => Instr::Inp,
+        Instr::Out => Instr::Out,
+        Instr::Set(val) => Instr::Set(val),
+        Instr::Add(val) => Instr::Add(val),
+        Instr::Mul(val) => Instr::Mul(val),
+        Instr::Div(val) => Instr::Div(val),
+        Instr::Dec => Instr::Dec,
+        Instr::Rnd(val) => Instr::Rnd(val),
+    }
+}
+
+pub fn resolve(
+    ast: Ast,
+) -> Result<(Vec<Instr>, Vec<Range<usize>>, HashMap<String, usize>), Vec<LabelError>> {
     let mut labels = HashMap::<String, usize>::new();
-    let mut spans = HashMap::<&str, Range<usize>>::new();
+    let mut label_spans = HashMap::<&str, Range<usize>>::new();
     let mut errs = Vec::new();
 
     let mut instr_idx = 0;
     for line in &ast {
-        if let AstLine::Label(token) = line {
-            if let Some(first) = spans.get(token.inner.as_str()) {
-                errs.push(LabelError::Redefined {
-                    label: token.inner.clone(),
-                    first: first.clone(),
-                    second: token.span.clone(),
-                });
-            } else {
-                labels.insert(token.inner.clone(), instr_idx);
-                spans.insert(&token.inner, token.span.clone());
-                debug!("found label {} @ {}", token.inner, instr_idx);
+        match line {
+            AstLine::Label(token) => {
+                if let Some(first) = label_spans.get(token.inner.as_str()) {
+                    errs.push(LabelError::Redefined {
+                        label: token.inner.clone(),
+                        first: first.clone(),
+                        second: token.span.clone(),
+                    });
+                } else {
+                    labels.insert(token.inner.clone(), instr_idx);
+                    label_spans.insert(&token.inner, token.span.clone());
+                    debug!("found label {} @ {}", token.inner, instr_idx);
+                }
             }
-        } else {
-            instr_idx += 1;
+            AstLine::Comment => {}
+            _ => instr_idx += 1,
         }
     }
 
     for line in &ast {
-        if let AstLine::Instr(Instr::Jmp(token) | Instr::Jnz(_, token) | Instr::Jlz(_, token)) =
+        if let AstLine::Instr(Instr::Jmp(token) | Instr::Jnz(_, token) | Instr::Jlz(_, token), _) =
             line
         {
-            if !spans.contains_key(token.inner.as_str()) {
+            if !label_spans.contains_key(token.inner.as_str()) {
                 errs.push(LabelError::Unknown(token.clone()))
             }
         }
     }
 
-    let program = ast
-        .into_iter()
-        .filter_map(|line| match line {
-            AstLine::Instr(i) => Some(i),
-            _ => None,
-        })
-        .collect();
+    if !errs.is_empty() {
+        return Err(errs);
+    }
 
-    if errs.is_empty() {
-        Ok((program, labels))
-    } else {
-        Err(errs)
+    let mut program = Vec::new();
+    let mut spans = Vec::new();
+    for line in ast {
+        if let AstLine::Instr(instr, span) = line {
+            program.push(lower(instr, &labels));
+            spans.push(span);
+        }
     }
+
+    Ok((program, spans, labels))
 }
@@ -0,0 +1,111 @@
+use crate::{args::ReplArgs, parse, parse_and_resolve, run::State, Resolved};
+
+use std::io::{self, Write};
+
+use anyhow::Error;
+use chumsky::{error::SimpleReason, prelude::Simple, Parser};
+
+/// Whether a parse error looks like the input simply ran out before an instruction, label, or
+/// block comment was finished, as opposed to an actual mistake. Used to decide whether to keep
+/// reading lines into the current entry or to report the errors and give up on it.
+pub(crate) fn is_unfinished(err: &Simple<char>) -> bool {
+    (matches!(err.reason(), SimpleReason::Unexpected) && err.found().is_none())
+        || matches!(err.reason(), SimpleReason::Custom(msg) if msg == "unterminated block comment")
+}
+
+/// Reads one entry from stdin, which may span multiple lines if the input parses as unfinished
+/// rather than outright wrong. Returns `None` at EOF.
+///
+/// Keeps reading as long as *any* error looks unfinished, not just when all of them do: once an
+/// unterminated block comment swallows the rest of the buffer, error recovery re-parses what
+/// comes after it as if the comment had never started, which can raise unrelated-looking errors
+/// alongside the real "still open" one.
+fn read_entry() -> Result<Option<String>, Error> {
+    let mut entry = String::new();
+
+    print!("> ");
+    io::stdout().flush()?;
+
+    loop {
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(if entry.is_empty() { None } else { Some(entry) });
+        }
+
+        let blank = line.trim().is_empty();
+        entry.push_str(&line);
+
+        if blank {
+            return Ok(Some(entry));
+        }
+
+        let (_, errs) = parse::root().parse_recovery(entry.as_str());
+        if errs.is_empty() || !errs.iter().any(is_unfinished) {
+            return Ok(Some(entry));
+        }
+
+        print!(". ");
+        io::stdout().flush()?;
+    }
+}
+
+/// Folds one entry into the running session. `committed` is the text behind the program
+/// currently loaded into `state`; `pending` is text from earlier entries that's never resolved
+/// on its own, kept around in case a later entry defines the label it's missing (e.g. a forward
+/// `jmp`). Returns the new `(committed, pending)` to keep for next time.
+///
+/// Tries `committed` + `pending` + `entry` first, so a forward reference can still resolve once
+/// its label shows up. If that fails on an unresolved label, falls back to trying `entry` against
+/// `committed` alone: a later, unrelated entry shouldn't be wedged forever just because some
+/// earlier entry's forward reference was never fulfilled (a typo'd or abandoned label, or one
+/// that got redefined instead of defined). That fallback succeeding runs the entry and folds it
+/// into `committed` while leaving `pending` untouched, still waiting on its own label.
+///
+/// A genuine parse error's text is dropped entirely, since there's no reason to expect a later
+/// entry to fix a syntax error in this one.
+///
+/// A whitespace-only entry (e.g. just pressing enter with nothing else typed yet) is a no-op:
+/// there's no program text in it to parse, and a bare newline doesn't match the grammar on its
+/// own since there's no preceding line for it to trail.
+pub fn submit(committed: &str, pending: &str, entry: &str, state: &mut State) -> Result<(String, String), Error> {
+    if entry.trim().is_empty() {
+        return Ok((committed.to_string(), pending.to_string()));
+    }
+
+    let candidate_pending = format!("{pending}{entry}");
+    let combined = format!("{committed}{candidate_pending}");
+
+    match parse_and_resolve(&combined, "<repl>") {
+        Resolved::Program(program, spans, _labels) => {
+            state.replace_program(program, spans);
+            state.run()?;
+            Ok((combined, String::new()))
+        }
+        Resolved::LabelFailed => {
+            let standalone = format!("{committed}{entry}");
+            match parse_and_resolve(&standalone, "<repl>") {
+                Resolved::Program(program, spans, _labels) => {
+                    state.replace_program(program, spans);
+                    state.run()?;
+                    Ok((standalone, pending.to_string()))
+                }
+                Resolved::LabelFailed | Resolved::ParseFailed => {
+                    Ok((committed.to_string(), candidate_pending))
+                }
+            }
+        }
+        Resolved::ParseFailed => Ok((committed.to_string(), pending.to_string())),
+    }
+}
+
+pub fn run(args: &ReplArgs) -> Result<(), Error> {
+    let mut committed = String::new();
+    let mut pending = String::new();
+    let mut state = State::new(Vec::new(), Vec::new(), args.seed);
+
+    while let Some(entry) = read_entry()? {
+        (committed, pending) = submit(&committed, &pending, &entry, &mut state)?;
+    }
+
+    Ok(())
+}
@@ -1,24 +1,32 @@
 use crate::{
     instrs::{Instr, Reg, Val},
-    parse::Token,
+    rng::Rng,
 };
 
-use std::{collections::HashMap, io, num::TryFromIntError};
+use std::{
+    hash::{BuildHasher, Hasher},
+    io,
+    num::TryFromIntError,
+    ops::Range,
+};
 
-use anyhow::{Context, Error};
+use anyhow::{bail, Context, Error};
 use log::trace;
 
-pub struct State<'a> {
+pub struct State {
     x: i64,
     acc: i64,
     tape: Vec<i64>,
     cursor: usize,
-    program: &'a [Instr],
-    labels: HashMap<String, usize>,
+    program: Vec<Instr>,
+    /// The source byte span of each instruction in `program`, parallel by index. Used by the
+    /// debugger to highlight the instruction that's about to execute.
+    spans: Vec<Range<usize>>,
     instr_ptr: usize,
     jumped: bool,
     input_buf: String,
     input_cursor: usize,
+    rng: Rng,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -27,20 +35,79 @@ enum ControlFlow {
     Exit,
 }
 
-impl<'a> State<'a> {
-    pub fn new(program: &'a [Instr], labels: HashMap<String, usize>) -> Self {
+impl State {
+    pub fn new(program: Vec<Instr>, spans: Vec<Range<usize>>, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            std::collections::hash_map::RandomState::new()
+                .build_hasher()
+                .finish()
+        });
+
         Self {
             x: 0,
             acc: 0,
             tape: Vec::new(),
             cursor: 0,
             program,
-            labels,
+            spans,
             instr_ptr: 0,
             jumped: false,
             input_buf: String::new(),
             input_cursor: 0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Replaces the program wholesale, keeping `instr_ptr` so execution continues from where it
+    /// left off on the next call to `run`. Used by the REPL, which re-resolves the cumulative
+    /// source on every entry and hands back the full program rather than just the new tail.
+    pub fn replace_program(&mut self, program: Vec<Instr>, spans: Vec<Range<usize>>) {
+        self.program = program;
+        self.spans = spans;
+    }
+
+    /// The index of the instruction about to execute.
+    pub fn instr_ptr(&self) -> usize {
+        self.instr_ptr
+    }
+
+    /// The source byte span of the instruction about to execute, if any remains.
+    pub fn current_span(&self) -> Option<Range<usize>> {
+        self.spans.get(self.instr_ptr).cloned()
+    }
+
+    /// Runs a single instruction, returning `false` without running anything if the program has
+    /// already exited, and `true` otherwise.
+    pub fn step(&mut self) -> Result<bool, Error> {
+        Ok(self.run_instr()? == ControlFlow::Continue)
+    }
+
+    /// The value under the cursor, for tests to check against without a register round-trip.
+    #[cfg(test)]
+    pub(crate) fn cur_value(&mut self) -> i64 {
+        self.cur()
+    }
+
+    /// Prints `x`, `acc`, and the tape with the cursor's position marked, for the debugger.
+    pub fn print_machine(&self) {
+        println!("x: {}, acc: {}", self.x, self.acc);
+        print!("tape:");
+        for (i, v) in self.tape.iter().enumerate() {
+            if i == self.cursor {
+                print!(" [{v}]");
+            } else {
+                print!(" {v}");
+            }
         }
+        // Cells from the end of the tape up to the cursor haven't been touched yet, so they're 0.
+        for i in self.tape.len()..=self.cursor {
+            if i == self.cursor {
+                print!(" [0]");
+            } else {
+                print!(" 0");
+            }
+        }
+        println!();
     }
 
     fn reg(&self, reg: Reg) -> i64 {
@@ -94,9 +161,9 @@ impl<'a> State<'a> {
             Instr::Gor => self.gor(),
             Instr::Get(reg) => self.get(*reg),
             Instr::Put(reg) => self.put(*reg),
-            Instr::Jmp(label) => self.jmp(label),
-            Instr::Jnz(reg, label) => self.jnz(*reg, label),
-            Instr::Jlz(reg, label) => self.jlz(*reg, label),
+            Instr::Jmp(target) => self.jmp(*target),
+            Instr::Jnz(reg, target) => self.jnz(*reg, *target),
+            Instr::Jlz(reg, target) => self.jlz(*reg, *target),
             Instr::Sav => self.sav(),
             Instr::Ret => self.ret().context("`ret` instruction failed")?,
             Instr::Inp => self.inp()?,
@@ -106,6 +173,7 @@ impl<'a> State<'a> {
             Instr::Mul(val) => self.mul(*val),
             Instr::Div(val) => self.div(*val),
             Instr::Dec => self.dec(),
+            Instr::Rnd(val) => self.rnd(*val).context("`rnd` instruction failed")?,
         }
 
         if self.jumped {
@@ -123,7 +191,7 @@ impl<'a> State<'a> {
     }
 }
 
-impl<'a> State<'a> {
+impl State {
     fn gol(&mut self) {
         self.cursor = self.cursor.saturating_sub(1);
     }
@@ -140,23 +208,23 @@ impl<'a> State<'a> {
         *self.reg_mut(reg) = self.cur();
     }
 
-    fn jmp(&mut self, label: &Token) {
-        self.instr_ptr = self.labels[&label.inner];
+    fn jmp(&mut self, target: usize) {
+        self.instr_ptr = target;
         self.jumped = true;
         trace!("    jumped");
     }
 
-    fn jnz(&mut self, reg: Reg, label: &Token) {
+    fn jnz(&mut self, reg: Reg, target: usize) {
         if self.reg(reg) != 0 {
-            self.jmp(label);
+            self.jmp(target);
         } else {
             trace!("    did not jump")
         }
     }
 
-    fn jlz(&mut self, reg: Reg, label: &Token) {
+    fn jlz(&mut self, reg: Reg, target: usize) {
         if self.reg(reg) < 0 {
-            self.jmp(label);
+            self.jmp(target);
         } else {
             trace!("    did not jump");
         }
@@ -220,4 +288,23 @@ impl<'a> State<'a> {
     fn dec(&mut self) {
         self.acc -= 1;
     }
+
+    fn rnd(&mut self, val: Val) -> Result<(), Error> {
+        let bound = match val {
+            Val::Num(0) => None,
+            Val::Num(n) if n < 0 => bail!("`rnd` bound must be positive, got {n}"),
+            Val::Num(n) => Some(n),
+            Val::X if self.x <= 0 => bail!("`rnd x` requires a positive value in `x`, got {}", self.x),
+            Val::X => Some(self.x),
+        };
+
+        let n = match bound {
+            Some(bound) => (self.rng.next_u64() % bound as u64) as i64,
+            None => self.rng.next_u64() as i64,
+        };
+
+        *self.cur_mut() = n;
+
+        Ok(())
+    }
 }
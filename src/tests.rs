@@ -0,0 +1,224 @@
+use crate::{
+    codegen,
+    instrs::{Instr, Reg, Val},
+    parse, repl,
+    rng::Rng,
+    run::State,
+};
+
+use std::collections::HashMap;
+
+use chumsky::{error::SimpleReason, Parser};
+
+#[test]
+fn nested_block_comments_are_skipped() {
+    let src = "#{ outer #{ inner }# still outer }#\ngol\n";
+
+    let (ast, errs) = parse::root().parse_recovery(src);
+    assert!(errs.is_empty(), "unexpected parse errors: {errs:?}");
+
+    let (program, _spans, _labels) = parse::resolve(ast.unwrap()).unwrap();
+    assert_eq!(program, vec![Instr::Gol]);
+}
+
+/// `ret` resumes one past the instruction that recorded the return address, since `run.rs`'s
+/// `ret()` never sets `jumped` and so always falls into the interpreter's own auto-increment.
+/// `gor`/`gol` move the cursor between the save and the return so a naive `ip = tape[cursor]`
+/// (no `+ 1`) in the generated C would diverge from this.
+#[test]
+fn ret_resumes_one_past_the_saved_instruction() {
+    let program = vec![Instr::Sav, Instr::Gor, Instr::Gol, Instr::Ret];
+    let spans = vec![0..0; program.len()];
+
+    let mut state = State::new(program.clone(), spans, Some(0));
+    for _ in 0..4 {
+        state.step().unwrap();
+    }
+    assert_eq!(state.instr_ptr(), 1);
+
+    let c = codegen::emit_c(&program, &HashMap::new(), None);
+    assert!(
+        c.contains("ip = (size_t)tape[cursor] + 1;"),
+        "generated `ret` case should match the interpreter's resume-past-the-save behavior: {c}"
+    );
+}
+
+#[test]
+fn unterminated_block_comment_errors_at_the_opening_span() {
+    let src = "#{ never closed\ngol\n";
+
+    let (_ast, errs) = parse::root().parse_recovery(src);
+    assert!(!errs.is_empty());
+
+    let unterminated = errs
+        .iter()
+        .find(|err| matches!(err.reason(), SimpleReason::Custom(msg) if msg == "unterminated block comment"))
+        .expect("expected an unterminated block comment error");
+    assert_eq!(unterminated.span(), 0..2);
+}
+
+#[test]
+fn trailing_comment_after_instruction_is_ignored() {
+    let src = "gol # move left\n";
+
+    let (ast, errs) = parse::root().parse_recovery(src);
+    assert!(errs.is_empty(), "unexpected parse errors: {errs:?}");
+
+    let (program, _spans, _labels) = parse::resolve(ast.unwrap()).unwrap();
+    assert_eq!(program, vec![Instr::Gol]);
+}
+
+/// An in-progress `#{ ... ` block comment spanning several physical lines should look unfinished
+/// on each line read so far, the same way `read_entry` checks it line by line, rather than
+/// reporting a hard error and abandoning the rest of the comment as garbage entries.
+#[test]
+fn unfinished_block_comment_is_treated_as_incomplete_input() {
+    let mut entry = String::new();
+    for line in ["#{ still open\n", "more comment\n"] {
+        entry.push_str(line);
+        let (_ast, errs) = parse::root().parse_recovery(entry.as_str());
+        assert!(!errs.is_empty(), "an open block comment should still error while unclosed");
+        assert!(
+            errs.iter().any(repl::is_unfinished),
+            "an in-progress block comment should look unfinished, not like a hard error: {errs:?}"
+        );
+    }
+
+    entry.push_str("}#\ngol\n");
+    let (ast, errs) = parse::root().parse_recovery(entry.as_str());
+    assert!(errs.is_empty(), "unexpected parse errors: {errs:?}");
+
+    let (program, _spans, _labels) = parse::resolve(ast.unwrap()).unwrap();
+    assert_eq!(program, vec![Instr::Gol]);
+}
+
+/// Pressing enter with nothing typed yet submits a bare `"\n"` entry against an empty `src`.
+/// That has no program text to parse, so it should be a silent no-op rather than a hard parse
+/// error, since a lone newline never matches the grammar on its own.
+#[test]
+fn blank_entry_against_empty_src_is_a_no_op() {
+    let mut state = State::new(Vec::new(), Vec::new(), Some(0));
+
+    let (committed, pending) = repl::submit("", "", "\n", &mut state).unwrap();
+    assert_eq!(committed, "", "a blank entry with no prior source should leave committed untouched");
+    assert_eq!(pending, "", "a blank entry with no prior source should leave pending untouched");
+}
+
+/// A `jmp` to a label that's only defined in a later entry should resolve once that entry is
+/// submitted, rather than being forgotten because the first entry failed to resolve.
+#[test]
+fn repl_resolves_forward_label_reference_across_entries() {
+    let mut state = State::new(Vec::new(), Vec::new(), Some(0));
+
+    let (committed, pending) = repl::submit("", "", "jmp foo\n", &mut state).unwrap();
+    assert_eq!(committed, "", "nothing has resolved yet, so nothing should be committed");
+    assert_eq!(pending, "jmp foo\n", "the unresolved entry's text should still be kept");
+
+    let (committed, pending) = repl::submit(&committed, &pending, "foo:\ndec\n", &mut state).unwrap();
+    assert_eq!(committed, "jmp foo\nfoo:\ndec\n");
+    assert_eq!(pending, "");
+    assert_eq!(state.instr_ptr(), 2, "the jmp should have landed on `dec` and then exited past it");
+}
+
+/// An entry with a forward reference that's never fulfilled (a typo'd or abandoned label) should
+/// only block itself, not every later, independent entry for the rest of the session.
+#[test]
+fn repl_unresolved_entry_does_not_wedge_later_independent_entries() {
+    let mut state = State::new(Vec::new(), Vec::new(), Some(0));
+
+    let (committed, pending) = repl::submit("", "", "jmp typo\n", &mut state).unwrap();
+    assert_eq!(committed, "", "the typo'd jump never resolves, so nothing should commit yet");
+    assert_eq!(pending, "jmp typo\n");
+
+    let (committed, pending) = repl::submit(&committed, &pending, "dec\n", &mut state).unwrap();
+    assert_eq!(committed, "dec\n", "an unrelated, valid entry should still commit and run");
+    assert_eq!(pending, "jmp typo\n", "the still-unresolved entry should be kept around");
+    assert_eq!(state.instr_ptr(), 1, "the independent `dec` should have run despite the stuck entry");
+
+    let (committed, pending) = repl::submit(&committed, &pending, "out\n", &mut state).unwrap();
+    assert_eq!(committed, "dec\nout\n", "further independent entries should keep running too");
+    assert_eq!(pending, "jmp typo\n");
+    assert_eq!(state.instr_ptr(), 2);
+}
+
+/// A negative literal bound is never a valid range to draw from, so `rnd` should report an error
+/// rather than the modulo silently wrapping it into something else.
+#[test]
+fn rnd_errors_on_negative_literal_bound() {
+    let program = vec![Instr::Rnd(Val::Num(-1))];
+    let spans = vec![0..0; program.len()];
+
+    let mut state = State::new(program, spans, Some(0));
+    assert!(state.step().is_err(), "`rnd` with a negative literal bound should error, not panic");
+}
+
+/// `rnd x` draws its bound from the `x` register at run time, so a non-positive `x` needs the
+/// same rejection a negative literal bound gets, just discovered later.
+#[test]
+fn rnd_errors_on_nonpositive_x_bound() {
+    for x in [0i64, -3] {
+        let program = vec![Instr::Set(Val::Num(x)), Instr::Put(Reg::X), Instr::Rnd(Val::X)];
+        let spans = vec![0..0; program.len()];
+
+        let mut state = State::new(program, spans, Some(0));
+        assert!(state.step().is_ok());
+        assert!(state.step().is_ok());
+        assert!(
+            state.step().is_err(),
+            "`rnd x` with x = {x} should error, not treat it as a valid bound"
+        );
+    }
+}
+
+/// A bounded `rnd` should only ever draw from `[0, bound)`, never the bound itself or anything
+/// negative.
+#[test]
+fn rnd_draws_within_the_given_bound() {
+    let bound = 5;
+    let program = vec![Instr::Rnd(Val::Num(bound))];
+    let spans = vec![0..0; program.len()];
+
+    for seed in 0..20 {
+        let mut state = State::new(program.clone(), spans.clone(), Some(seed));
+        state.step().unwrap();
+        let n = state.cur_value();
+        assert!((0..bound).contains(&n), "rnd {bound} drew {n}, outside [0, {bound})");
+    }
+}
+
+/// `rnd 0` draws from the whole `i64` range rather than treating `0` as an empty bound, matching
+/// a bare draw from the underlying RNG with no modulo applied.
+#[test]
+fn rnd_with_zero_bound_draws_full_range() {
+    let seed = 42;
+    let program = vec![Instr::Rnd(Val::Num(0))];
+    let spans = vec![0..0; program.len()];
+
+    let mut state = State::new(program, spans, Some(seed));
+    state.step().unwrap();
+
+    let mut rng = Rng::new(seed);
+    let expected = rng.next_u64() as i64;
+    assert_eq!(state.cur_value(), expected, "`rnd 0` should draw straight from the RNG, unmodulated");
+}
+
+/// Two runs seeded identically should draw the exact same sequence from `rnd`, which is the
+/// whole point of `--seed` letting a run be reproduced.
+#[test]
+fn rnd_is_deterministic_given_the_same_seed() {
+    let program = vec![Instr::Rnd(Val::Num(1000)), Instr::Gor, Instr::Rnd(Val::Num(1000))];
+    let spans = vec![0..0; program.len()];
+
+    let draw = |seed| {
+        let mut state = State::new(program.clone(), spans.clone(), Some(seed));
+        let mut draws = Vec::new();
+        for _ in 0..program.len() {
+            state.step().unwrap();
+            draws.push(state.cur_value());
+        }
+        draws
+    };
+
+    assert_eq!(draw(7), draw(7), "the same seed should reproduce the same sequence of draws");
+    assert_ne!(draw(7), draw(8), "different seeds should (almost always) diverge");
+}